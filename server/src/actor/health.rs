@@ -4,12 +4,118 @@
 use crate::{ArenaService, ContinuousExtremaMetricAccumulator};
 use log::error;
 use simple_server_status::SimpleServerStatus;
+use std::collections::HashMap;
 use std::mem;
+use std::os::unix::io::RawFd;
 use std::time::{Duration, Instant};
 
+/// Abstracts over the source of aggregate OS-level health metrics, so the
+/// real sampler (backed by `/proc` and friends) can be swapped for a fake
+/// one in environments where it isn't available.
+pub trait HealthSource {
+    fn update(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    fn cpu_usage(&self) -> Option<f32>;
+    fn cpu_stolen_usage(&self) -> Option<f32>;
+    fn ram_usage(&self) -> Option<f32>;
+    fn ram_swap_usage(&self) -> Option<f32>;
+}
+
+impl HealthSource for SimpleServerStatus {
+    fn update(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        SimpleServerStatus::update(self).map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+    }
+
+    fn cpu_usage(&self) -> Option<f32> {
+        SimpleServerStatus::cpu_usage(self)
+    }
+
+    fn cpu_stolen_usage(&self) -> Option<f32> {
+        SimpleServerStatus::cpu_stolen_usage(self)
+    }
+
+    fn ram_usage(&self) -> Option<f32> {
+        SimpleServerStatus::ram_usage(self)
+    }
+
+    fn ram_swap_usage(&self) -> Option<f32> {
+        SimpleServerStatus::ram_swap_usage(self)
+    }
+}
+
+/// A single socket's `TCP_INFO` snapshot.
+#[derive(Default, Clone, Copy)]
+struct TcpSample {
+    rtt_secs: f32,
+    retransmit_rate: f32,
+    /// Fraction (0 to 1) of the send congestion window currently occupied
+    /// by in-flight, unacknowledged segments.
+    send_window_occupancy: f32,
+    /// Fraction (0 to 1) of the receive buffer currently occupied, i.e. not
+    /// yet drained by the application.
+    recv_window_occupancy: f32,
+    rx_bytes: u64,
+    tx_bytes: u64,
+}
+
+/// Why `sample_tcp_info` couldn't produce a sample.
+enum TcpInfoError {
+    /// The socket is closed/invalid (`EBADF`/`ENOTCONN`); stop tracking it.
+    Closed,
+    /// Some other, presumably transient, failure; keep tracking the socket
+    /// and just skip this sample.
+    Transient,
+}
+
+#[cfg(target_os = "linux")]
+fn sample_tcp_info(fd: RawFd) -> Result<TcpSample, TcpInfoError> {
+    let mut info: libc::tcp_info = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::tcp_info>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::IPPROTO_TCP,
+            libc::TCP_INFO,
+            &mut info as *mut _ as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if ret != 0 {
+        return Err(match std::io::Error::last_os_error().raw_os_error() {
+            Some(libc::EBADF) | Some(libc::ENOTCONN) => TcpInfoError::Closed,
+            _ => TcpInfoError::Transient,
+        });
+    }
+
+    let segs_out = (info.tcpi_segs_out as f32).max(1.0);
+    let snd_cwnd = (info.tcpi_snd_cwnd as f32).max(1.0);
+    let rcv_ssthresh = (info.tcpi_rcv_ssthresh as f32).max(1.0);
+    Ok(TcpSample {
+        rtt_secs: info.tcpi_rtt as f32 / 1_000_000.0,
+        retransmit_rate: info.tcpi_total_retrans as f32 / segs_out,
+        send_window_occupancy: (info.tcpi_unacked as f32 / snd_cwnd).clamp(0.0, 1.0),
+        recv_window_occupancy: (1.0 - info.tcpi_rcv_space as f32 / rcv_ssthresh).clamp(0.0, 1.0),
+        rx_bytes: info.tcpi_bytes_received,
+        tx_bytes: info.tcpi_bytes_acked,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn sample_tcp_info(_fd: RawFd) -> Result<TcpSample, TcpInfoError> {
+    Err(TcpInfoError::Transient)
+}
+
+/// The last `TCP_INFO` byte counters seen for a tracked socket, so bandwidth
+/// can be derived from that socket's own delta rather than a delta of sums
+/// (which breaks as sockets come and go between refreshes).
+#[derive(Default)]
+struct SocketCounters {
+    rx_bytes: u64,
+    tx_bytes: u64,
+}
+
 /// Keeps track of the "health" of the server.
 pub struct Health {
-    system: SimpleServerStatus,
+    system: Box<dyn HealthSource + Send>,
     last: Instant,
     /// Cached CPU fraction.
     cpu: f32,
@@ -30,12 +136,44 @@ pub struct Health {
     ticks: usize,
     /// Start of TPS measurement.
     tps_start: Instant,
+    /// Accepted connection sockets to sample `TCP_INFO` from, along with
+    /// each one's last-seen byte counters.
+    sockets: HashMap<RawFd, SocketCounters>,
+    /// Cached smoothed round-trip time, in seconds, averaged over sockets
+    /// and HTTP/3/QUIC samples.
+    rtt: f32,
+    /// Cached retransmission rate (retransmitted / sent segments), averaged over sockets.
+    retransmit_rate: f32,
+    /// Cached send window occupancy (0 to 1), averaged over sockets.
+    send_window_occupancy: f32,
+    /// Cached receive window occupancy (0 to 1), averaged over sockets.
+    recv_window_occupancy: f32,
+    /// Cached bytes/second received, derived from `TCP_INFO` byte counters.
+    bandwidth_rx: u64,
+    /// Cached bytes/second transmitted, derived from `TCP_INFO` byte counters.
+    bandwidth_tx: u64,
+    /// RTT samples reported by HTTP/3/QUIC connections since the last refresh.
+    quic_rtt: ContinuousExtremaMetricAccumulator,
+    /// Congestion window samples reported by HTTP/3/QUIC connections.
+    quic_cwnd: ContinuousExtremaMetricAccumulator,
+    /// Cached congestion window, in bytes, averaged over QUIC connections.
+    congestion_window: f32,
 }
 
 impl Health {
     /// How long to cache data for (getting data is relatively expensive).
     const CACHE: Duration = Duration::from_secs(30);
 
+    /// Build a `Health` sampling OS-level metrics from `source` instead of
+    /// the default `SimpleServerStatus`, e.g. a fake source for tests or
+    /// environments where real sampling isn't available.
+    pub fn with_source(source: impl HealthSource + Send + 'static) -> Self {
+        Self {
+            system: Box::new(source),
+            ..Self::default()
+        }
+    }
+
     /// Get (possibly cached) cpu usage from 0 to 1.
     pub fn cpu(&mut self) -> f32 {
         self.refresh_if_necessary();
@@ -60,22 +198,81 @@ impl Health {
         self.missed_ticks
     }
 
-    /// Get (possibly cached) bytes/second received.
+    /// Get (possibly cached) bytes/second received, measured via `TCP_INFO`
+    /// on tracked sockets.
     pub fn bandwidth_rx(&mut self) -> u64 {
-        // 返回固定带宽值（100MB/s），不进行实际系统检查
-        100_000_000
+        self.refresh_if_necessary();
+        self.bandwidth_rx
     }
 
-    /// Get (possibly cached) bytes/second transmitted.
+    /// Get (possibly cached) bytes/second transmitted, measured via
+    /// `TCP_INFO` on tracked sockets.
     pub fn bandwidth_tx(&mut self) -> u64 {
-        // 返回固定带宽值（50MB/s），不进行实际系统检查
-        50_000_000
+        self.refresh_if_necessary();
+        self.bandwidth_tx
     }
 
     /// Get (possibly cached) TCP/UDP connection/socket count.
+    /// Counts the sockets registered via [`Health::track_socket`];
+    /// HTTP/3/QUIC connections are not counted.
     pub fn connections(&mut self) -> usize {
-        // 返回固定连接数，不进行实际系统检查
-        100
+        self.refresh_if_necessary();
+        self.sockets.len()
+    }
+
+    /// Get (possibly cached) smoothed round-trip time, in seconds: the mean
+    /// over all tracked sockets, averaged with the mean of HTTP/3/QUIC
+    /// samples from [`Health::record_quic_sample`] when there are both.
+    pub fn rtt(&mut self) -> f32 {
+        self.refresh_if_necessary();
+        self.rtt
+    }
+
+    /// Get (possibly cached) retransmission rate, averaged over all tracked
+    /// sockets.
+    pub fn retransmit_rate(&mut self) -> f32 {
+        self.refresh_if_necessary();
+        self.retransmit_rate
+    }
+
+    /// Get (possibly cached) send window occupancy (0 to 1): how full the
+    /// send congestion window is with in-flight, unacknowledged segments,
+    /// averaged over all tracked sockets.
+    pub fn send_window_occupancy(&mut self) -> f32 {
+        self.refresh_if_necessary();
+        self.send_window_occupancy
+    }
+
+    /// Get (possibly cached) receive window occupancy (0 to 1): how full
+    /// the receive buffer is with data the application hasn't drained yet,
+    /// averaged over all tracked sockets.
+    pub fn recv_window_occupancy(&mut self) -> f32 {
+        self.refresh_if_necessary();
+        self.recv_window_occupancy
+    }
+
+    /// Start sampling `TCP_INFO` from an accepted connection's socket.
+    pub fn track_socket(&mut self, fd: RawFd) {
+        self.sockets.insert(fd, SocketCounters::default());
+    }
+
+    /// Stop sampling a socket, e.g. because the connection closed.
+    pub fn untrack_socket(&mut self, fd: RawFd) {
+        self.sockets.remove(&fd);
+    }
+
+    /// Get (possibly cached) congestion window, in bytes, averaged over
+    /// HTTP/3/QUIC connections reporting via [`Health::record_quic_sample`].
+    pub fn congestion_window(&mut self) -> f32 {
+        self.refresh_if_necessary();
+        self.congestion_window
+    }
+
+    /// Report an HTTP/3/QUIC connection's current RTT and congestion window
+    /// estimate, so it contributes to the same transport telemetry as TCP.
+    pub fn record_quic_sample(&mut self, rtt_secs: f32, cwnd_bytes: f32) {
+        self.quic_rtt.push(rtt_secs);
+        self.quic_cwnd.push(cwnd_bytes);
     }
 
     /// Call to get average TPS over a large interval.
@@ -120,20 +317,12 @@ impl Health {
     }
 
     fn refresh_if_necessary(&mut self) {
-        if self.last.elapsed() <= Self::CACHE {
+        let elapsed = self.last.elapsed();
+        if elapsed <= Self::CACHE {
             return;
         }
         self.last = Instant::now();
-        
-        // 不再进行实际系统检查，直接设置固定值
-        // 这样可以避免在Render环境中出现文件找不到的错误
-        self.cpu = 0.15;        // 15% CPU使用率
-        self.cpu_steal = 0.0;    // 0% CPU窃取时间
-        self.ram = 0.3;          // 30% RAM使用率
-        self.swap = 0.0;         // 0% 交换空间使用率
-        
-        // 注释掉原有的系统检查代码
-        /*
+
         // Health may fail on local system due to lack of conntrack.
         if let Err(e) = self.system.update()
             && cfg!(not(debug_assertions))
@@ -145,7 +334,75 @@ impl Health {
         self.cpu_steal = self.system.cpu_stolen_usage().unwrap_or(0.0);
         self.ram = self.system.ram_usage().unwrap_or(0.0);
         self.swap = self.system.ram_swap_usage().unwrap_or(0.0);
-        */
+
+        self.refresh_transport_metrics(elapsed);
+    }
+
+    /// Samples `TCP_INFO` from all tracked sockets and folds in any
+    /// HTTP/3/QUIC samples reported since the last refresh, rolling both up
+    /// into the cached `rtt`/`retransmit_rate`/`bandwidth_rx`/`bandwidth_tx`/
+    /// `congestion_window` fields. Sockets that no longer resolve (e.g. the
+    /// connection closed) are dropped, which keeps `connections()` accurate
+    /// for free.
+    fn refresh_transport_metrics(&mut self, elapsed: Duration) {
+        let mut rtt_acc = ContinuousExtremaMetricAccumulator::default();
+        let mut retransmit_acc = ContinuousExtremaMetricAccumulator::default();
+        let mut send_window_acc = ContinuousExtremaMetricAccumulator::default();
+        let mut recv_window_acc = ContinuousExtremaMetricAccumulator::default();
+        let mut rx_delta_total = 0u64;
+        let mut tx_delta_total = 0u64;
+
+        self.sockets.retain(|&fd, counters| {
+            let sample = match sample_tcp_info(fd) {
+                Ok(sample) => sample,
+                // Keep transiently-failing sockets tracked; only a
+                // definitively closed fd should stop being sampled. This
+                // also matters on non-Linux, where `sample_tcp_info` always
+                // fails: without this, every tracked socket would be
+                // untracked on the first refresh.
+                Err(TcpInfoError::Transient) => return true,
+                Err(TcpInfoError::Closed) => return false,
+            };
+            rtt_acc.push(sample.rtt_secs);
+            retransmit_acc.push(sample.retransmit_rate);
+            send_window_acc.push(sample.send_window_occupancy);
+            recv_window_acc.push(sample.recv_window_occupancy);
+            // Per-fd delta, not a delta of sums: the tracked set changes
+            // between refreshes, so summing first would under/over-count.
+            rx_delta_total =
+                rx_delta_total.saturating_add(sample.rx_bytes.saturating_sub(counters.rx_bytes));
+            tx_delta_total =
+                tx_delta_total.saturating_add(sample.tx_bytes.saturating_sub(counters.tx_bytes));
+            counters.rx_bytes = sample.rx_bytes;
+            counters.tx_bytes = sample.tx_bytes;
+            true
+        });
+
+        let quic_rtt = mem::take(&mut self.quic_rtt);
+        let quic_cwnd = mem::take(&mut self.quic_cwnd);
+
+        self.rtt = match (rtt_acc.mean(), quic_rtt.mean()) {
+            (Some(tcp), Some(quic)) => (tcp + quic) * 0.5,
+            (Some(tcp), None) => tcp,
+            (None, Some(quic)) => quic,
+            (None, None) => self.rtt,
+        };
+        if let Some(mean) = retransmit_acc.mean() {
+            self.retransmit_rate = mean;
+        }
+        if let Some(mean) = send_window_acc.mean() {
+            self.send_window_occupancy = mean;
+        }
+        if let Some(mean) = recv_window_acc.mean() {
+            self.recv_window_occupancy = mean;
+        }
+        if let Some(mean) = quic_cwnd.mean() {
+            self.congestion_window = mean;
+        }
+
+        let elapsed_secs = elapsed.as_secs_f32().max(1.0);
+        self.bandwidth_rx = (rx_delta_total as f32 / elapsed_secs) as u64;
+        self.bandwidth_tx = (tx_delta_total as f32 / elapsed_secs) as u64;
     }
 }
 
@@ -153,12 +410,12 @@ impl Default for Health {
     fn default() -> Self {
         let now = Instant::now();
         Self {
-            system: SimpleServerStatus::default(),
+            system: Box::new(SimpleServerStatus::default()),
             last: now - Self::CACHE * 2,
-            cpu: 0.15,      // 设置默认固定值
-            cpu_steal: 0.0, // 设置默认固定值
-            ram: 0.3,       // 设置默认固定值
-            swap: 0.0,      // 设置默认固定值
+            cpu: 0.0,
+            cpu_steal: 0.0,
+            ram: 0.0,
+            swap: 0.0,
             missed_ticks: 0.0,
             missed_ticks_start: now,
             ticks_for_missed_ticks: 0,
@@ -166,8 +423,16 @@ impl Default for Health {
             spt: ContinuousExtremaMetricAccumulator::default(),
             tps: ContinuousExtremaMetricAccumulator::default(),
             tps_start: now,
+            sockets: HashMap::new(),
+            rtt: 0.0,
+            retransmit_rate: 0.0,
+            send_window_occupancy: 0.0,
+            recv_window_occupancy: 0.0,
+            bandwidth_rx: 0,
+            bandwidth_tx: 0,
+            quic_rtt: ContinuousExtremaMetricAccumulator::default(),
+            quic_cwnd: ContinuousExtremaMetricAccumulator::default(),
+            congestion_window: 0.0,
         }
     }
 }
-
-
@@ -0,0 +1,89 @@
+// SPDX-FileCopyrightText: 2024 Softbear, Inc.
+// SPDX-License-Identifier: LGPL-3.0-or-later
+
+use crate::actor::health::Health;
+use std::time::{Duration, Instant};
+
+/// Why the admit limit changed (or didn't) on the last `update`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadDecision {
+    /// Server has headroom; `admit` was increased by a fixed increment.
+    AdditiveIncrease,
+    /// Server is under pressure; `admit` was cut by a multiplicative factor.
+    MultiplicativeDecrease,
+    /// Not enough time has passed since the last window to make a decision.
+    Unchanged,
+}
+
+/// AIMD (additive-increase/multiplicative-decrease) load shedder, the same
+/// control scheme TCP congestion control uses. Consumes [`Health::cpu`] and
+/// [`Health::missed_ticks`] once per window and adjusts how much work
+/// (bots, new connections, per-client update rate, ...) the server admits.
+pub struct LoadController {
+    /// How much work is currently admitted. Callers interpret the unit
+    /// (bot count, connections, update rate, ...).
+    admit: f32,
+    /// Floor below which `admit` will not be decreased further.
+    floor: f32,
+    /// Ceiling above which `admit` will not be increased further.
+    ceiling: f32,
+    window_start: Instant,
+    last_decision: LoadDecision,
+}
+
+impl LoadController {
+    /// How often the control loop re-evaluates.
+    const WINDOW: Duration = Duration::from_secs(30);
+    /// Additive increase per window, in `admit` units.
+    const INCREMENT: f32 = 1.0;
+    /// Multiplicative decrease factor.
+    const BACKOFF: f32 = 0.7;
+    /// Target missed-tick fraction above which the server is considered
+    /// under pressure.
+    const TARGET_MISSED_TICKS: f32 = 0.02;
+    /// CPU fraction above which the server is considered under pressure.
+    const TARGET_CPU: f32 = 0.85;
+
+    pub fn new(initial: f32, floor: f32, ceiling: f32) -> Self {
+        Self {
+            admit: initial.clamp(floor, ceiling),
+            floor,
+            ceiling,
+            window_start: Instant::now(),
+            last_decision: LoadDecision::Unchanged,
+        }
+    }
+
+    /// Current admitted amount of work.
+    pub fn admit(&self) -> f32 {
+        self.admit
+    }
+
+    /// Reason for the most recent change (or lack thereof) to `admit`.
+    pub fn last_decision(&self) -> LoadDecision {
+        self.last_decision
+    }
+
+    /// Call periodically (e.g. once per tick) with the current `Health`.
+    /// Only takes effect once per `WINDOW`; cheap to call more often.
+    pub fn update(&mut self, health: &mut Health) -> LoadDecision {
+        let now = Instant::now();
+        if now.duration_since(self.window_start) < Self::WINDOW {
+            self.last_decision = LoadDecision::Unchanged;
+            return self.last_decision;
+        }
+        self.window_start = now;
+
+        let under_pressure =
+            health.missed_ticks() >= Self::TARGET_MISSED_TICKS || health.cpu() >= Self::TARGET_CPU;
+
+        self.last_decision = if under_pressure {
+            self.admit = (self.admit * Self::BACKOFF).max(self.floor);
+            LoadDecision::MultiplicativeDecrease
+        } else {
+            self.admit = (self.admit + Self::INCREMENT).min(self.ceiling);
+            LoadDecision::AdditiveIncrease
+        };
+        self.last_decision
+    }
+}
@@ -1,6 +1,7 @@
 // SPDX-FileCopyrightText: 2024 Softbear, Inc.
 // SPDX-License-Identifier: LGPL-3.0-or-later
 
+use crate::net::congestion::CongestionControlKind;
 use crate::{RegionId, ServerId, ServerKind, ServerToken};
 use clap::Parser;
 use log::LevelFilter;
@@ -59,7 +60,22 @@ pub struct Options {
     
     #[clap(long)]
     pub https_port: Option<u16>,
-    
+
+    /// Serve HTTP/2 over cleartext (h2c) on `http_port`, via both the
+    /// HTTP/1 `Upgrade` handshake and prior-knowledge preface detection.
+    /// Useful behind load balancers/health checkers that terminate TLS.
+    #[clap(long)]
+    pub h2c: bool,
+
+    /// Serve HTTP/3 over QUIC on this UDP port, reusing the HTTPS
+    /// certificate material.
+    #[clap(long)]
+    pub h3_port: Option<u16>,
+
+    /// Congestion control algorithm for the HTTP/3/QUIC listener.
+    #[clap(long, value_enum, default_value = "cubic")]
+    pub congestion_control: CongestionControlKind,
+
     /// Override the region id.
     #[clap(long)]
     pub region_id: Option<RegionId>,
@@ -97,12 +113,35 @@ pub struct Options {
     /// Client authenticate rate limiting burst.
     #[clap(long, default_value = "16")]
     pub client_authenticate_burst: u32,
-    
+
+    /// Per-client request rate limiting, in requests per second. IPv6
+    /// clients are grouped by their /64 prefix.
+    #[clap(long, default_value = "20")]
+    pub client_rate_limit: f32,
+
+    /// Per-client request rate limiting burst.
+    #[clap(long, default_value = "40")]
+    pub client_rate_limit_burst: f32,
+
     #[clap(long)]
     pub cpu_profile: bool,
-    
+
     #[clap(long)]
     pub heap_profile: bool,
+
+    /// Enable `TCP_FASTOPEN` on listening sockets, with the given pending
+    /// SYN queue length, to cut handshake RTTs for reconnecting clients.
+    #[clap(long)]
+    pub tcp_fast_open: Option<u32>,
+
+    /// Enable server-side TCP keepalive on accepted sockets, probing after
+    /// this many idle seconds (on Linux), so dead NAT-bound clients are reaped.
+    #[clap(long)]
+    pub tcp_keepalive_secs: Option<u32>,
+
+    /// Override the socket send-buffer size (`SO_SNDBUF`), in bytes.
+    #[clap(long)]
+    pub tcp_send_buffer_size: Option<u32>,
 }
 
 impl Options {
@@ -175,4 +214,44 @@ impl Options {
         log::info!("HTTP port: {}, HTTPS port: {}", ports.0, ports.1);
         ports
     }
+
+    /// Applies `tcp_fast_open`/`tcp_keepalive_secs`/`tcp_send_buffer_size`
+    /// to a listening socket, per [`crate::net::socket::apply_tcp_tuning`].
+    pub(crate) fn apply_tcp_tuning(&self, fd: std::os::unix::io::RawFd) -> std::io::Result<()> {
+        crate::net::socket::apply_tcp_tuning(
+            fd,
+            self.tcp_fast_open,
+            self.tcp_keepalive_secs,
+            self.tcp_send_buffer_size,
+        )
+    }
+
+    /// Decides how to serve a freshly accepted `http_port` connection, per
+    /// [`crate::net::h2c::negotiate_protocol`].
+    pub(crate) fn negotiate_h2c(
+        &self,
+        peeked: &[u8],
+        connection_header: Option<&str>,
+        upgrade_header: Option<&str>,
+    ) -> crate::net::h2c::ConnectionProtocol {
+        crate::net::h2c::negotiate_protocol(self.h2c, peeked, connection_header, upgrade_header)
+    }
+
+    /// Spawns the HTTP/3/QUIC listener if `h3_port` is configured, reusing
+    /// `tls_config` from the HTTPS listener and answering requests via
+    /// `router` (the same routing the HTTP/1 and h2c listeners use). See
+    /// [`crate::net::quic::run_h3_listener`].
+    pub(crate) fn spawn_h3_listener(
+        &self,
+        tls_config: std::sync::Arc<rustls::ServerConfig>,
+        health: std::sync::Arc<std::sync::Mutex<crate::actor::health::Health>>,
+        router: crate::net::quic::H3Router,
+    ) -> Option<tokio::task::JoinHandle<std::io::Result<()>>> {
+        let h3_port = self.h3_port?;
+        let congestion_control = self.congestion_control;
+        Some(tokio::spawn(async move {
+            crate::net::quic::run_h3_listener(h3_port, congestion_control, tls_config, health, router)
+                .await
+        }))
+    }
 }
@@ -0,0 +1,148 @@
+// SPDX-FileCopyrightText: 2024 Softbear, Inc.
+// SPDX-License-Identifier: LGPL-3.0-or-later
+
+use clap::ValueEnum;
+
+/// Which loss-based congestion control algorithm a QUIC/HTTP-3 connection
+/// should use. Selected per-server via `Options::congestion_control`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CongestionControlKind {
+    NewReno,
+    Cubic,
+}
+
+impl CongestionControlKind {
+    pub fn build(self, max_datagram_size: f32) -> Box<dyn CongestionController> {
+        match self {
+            Self::NewReno => Box::new(NewReno::new(max_datagram_size)),
+            Self::Cubic => Box::new(Cubic::new(max_datagram_size)),
+        }
+    }
+}
+
+/// A loss-based congestion controller, driven by ack/loss events and
+/// queried for the current congestion window.
+pub trait CongestionController: Send + std::fmt::Debug {
+    /// Congestion window, in bytes.
+    fn cwnd(&self) -> f32;
+    /// Call when `acked_bytes` worth of data has been acknowledged, `rtt`
+    /// seconds after it was sent.
+    fn on_ack(&mut self, now: f32, acked_bytes: f32, rtt: f32);
+    /// Call when loss is detected.
+    fn on_loss(&mut self, now: f32);
+}
+
+/// Standard NewReno: slow start doubles `cwnd` per RTT until `ssthresh`,
+/// then congestion avoidance grows it by one MSS per RTT; on loss, halve
+/// `cwnd` and set `ssthresh` to the halved value.
+#[derive(Debug)]
+pub struct NewReno {
+    mss: f32,
+    cwnd: f32,
+    ssthresh: f32,
+    acked_in_round: f32,
+}
+
+impl NewReno {
+    fn new(mss: f32) -> Self {
+        Self {
+            mss,
+            cwnd: mss * 10.0,
+            ssthresh: f32::MAX,
+            acked_in_round: 0.0,
+        }
+    }
+}
+
+impl CongestionController for NewReno {
+    fn cwnd(&self) -> f32 {
+        self.cwnd
+    }
+
+    fn on_ack(&mut self, _now: f32, acked_bytes: f32, _rtt: f32) {
+        if self.cwnd < self.ssthresh {
+            // Slow start: one MSS of growth per acked MSS.
+            self.cwnd += acked_bytes;
+        } else {
+            // Congestion avoidance: one MSS of growth per RTT's worth of acks.
+            self.acked_in_round += acked_bytes;
+            if self.acked_in_round >= self.cwnd {
+                self.acked_in_round = 0.0;
+                self.cwnd += self.mss;
+            }
+        }
+    }
+
+    fn on_loss(&mut self, _now: f32) {
+        self.cwnd = (self.cwnd * 0.5).max(self.mss * 2.0);
+        self.ssthresh = self.cwnd;
+    }
+}
+
+/// CUBIC congestion control (RFC 8312-style). Grows the window as a cubic
+/// function of time since the last loss event: `W(t) = C*(t - K)^3 + W_max`,
+/// where `K = cbrt(W_max * (1 - beta) / C)` and `beta` is the multiplicative
+/// decrease factor, with windows counted in MSS-sized segments. Falls back
+/// to NewReno-style additive growth whenever that would be faster
+/// (TCP-friendliness region).
+///
+/// Before the first loss there is no congestion epoch to measure `t` from,
+/// so `cwnd` tracks plain NewReno (slow start, then congestion avoidance)
+/// instead of the cubic curve — otherwise a long-lived, app-limited
+/// connection would grow `cwnd` purely with wall-clock time regardless of
+/// how much data it actually acked.
+#[derive(Debug)]
+pub struct Cubic {
+    mss: f32,
+    cwnd: f32,
+    w_max: f32,
+    k: f32,
+    /// `None` until the first loss event starts a congestion epoch.
+    epoch_start: Option<f32>,
+    newreno: NewReno,
+}
+
+impl Cubic {
+    /// Cubic scaling constant, per RFC 8312.
+    const C: f32 = 0.4;
+    /// Multiplicative decrease factor on loss.
+    const BETA: f32 = 0.7;
+
+    fn new(mss: f32) -> Self {
+        Self {
+            mss,
+            cwnd: mss * 10.0,
+            w_max: mss * 10.0,
+            k: 0.0,
+            epoch_start: None,
+            newreno: NewReno::new(mss),
+        }
+    }
+}
+
+impl CongestionController for Cubic {
+    fn cwnd(&self) -> f32 {
+        self.cwnd
+    }
+
+    fn on_ack(&mut self, now: f32, acked_bytes: f32, rtt: f32) {
+        self.newreno.on_ack(now, acked_bytes, rtt);
+
+        self.cwnd = match self.epoch_start {
+            None => self.newreno.cwnd(),
+            Some(epoch_start) => {
+                let t = (now - epoch_start).max(0.0);
+                let cubic_cwnd = Self::C * (t - self.k).powi(3) * self.mss + self.w_max;
+                cubic_cwnd.max(self.newreno.cwnd()).max(self.mss)
+            }
+        };
+    }
+
+    fn on_loss(&mut self, now: f32) {
+        self.w_max = self.cwnd;
+        self.cwnd = (self.cwnd * Self::BETA).max(self.mss * 2.0);
+        self.k = (self.w_max / self.mss * (1.0 - Self::BETA) / Self::C).cbrt();
+        self.epoch_start = Some(now);
+        self.newreno.on_loss(now);
+    }
+}
@@ -0,0 +1,73 @@
+// SPDX-FileCopyrightText: 2024 Softbear, Inc.
+// SPDX-License-Identifier: LGPL-3.0-or-later
+
+/// The fixed preface an HTTP/2 client sends first when connecting with
+/// "prior knowledge" (i.e. skipping the HTTP/1 `Upgrade` handshake).
+pub const H2_PRIOR_KNOWLEDGE_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// Returns `true` if `peeked` (the first bytes read from a freshly accepted
+/// connection, without consuming them) looks like an HTTP/2 prior-knowledge
+/// preface rather than HTTP/1.
+pub fn is_h2_prior_knowledge(peeked: &[u8]) -> bool {
+    let len = peeked.len().min(H2_PRIOR_KNOWLEDGE_PREFACE.len());
+    len > 0 && peeked[..len] == H2_PRIOR_KNOWLEDGE_PREFACE[..len]
+}
+
+/// Whether an HTTP/1 request is asking to be upgraded to h2c, i.e. it has
+/// `Connection: Upgrade` and `Upgrade: h2c` headers.
+pub fn wants_h2c_upgrade(connection_header: Option<&str>, upgrade_header: Option<&str>) -> bool {
+    let connection_has_upgrade = connection_header
+        .map(|value| value.split(',').any(|token| token.trim().eq_ignore_ascii_case("upgrade")))
+        .unwrap_or(false);
+    let upgrade_is_h2c = upgrade_header
+        .map(|value| value.eq_ignore_ascii_case("h2c"))
+        .unwrap_or(false);
+    connection_has_upgrade && upgrade_is_h2c
+}
+
+/// The raw `101 Switching Protocols` response a server sends to accept an
+/// `Upgrade: h2c` request, per RFC 7540 §3.2, before handing the connection
+/// off to an HTTP/2 codec.
+pub const H2C_UPGRADE_RESPONSE: &[u8] =
+    b"HTTP/1.1 101 Switching Protocols\r\nConnection: Upgrade\r\nUpgrade: h2c\r\n\r\n";
+
+/// How a freshly accepted plaintext connection on `http_port` should be
+/// served, decided once per connection by [`negotiate_protocol`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionProtocol {
+    /// Serve as plain HTTP/1.1.
+    Http1,
+    /// Client sent the H2 prior-knowledge preface; hand the raw stream
+    /// straight to an HTTP/2 codec, no response needed first.
+    Http2PriorKnowledge,
+    /// Client sent an HTTP/1 request with `Upgrade: h2c`; write
+    /// [`H2C_UPGRADE_RESPONSE`], then hand the stream to an HTTP/2 codec.
+    Http2Upgrade,
+}
+
+/// Decides how to serve a freshly accepted connection on `http_port`, given
+/// `Options::h2c`. `peeked` is the first bytes read from the socket without
+/// consuming them. When they aren't a prior-knowledge preface,
+/// `connection_header`/`upgrade_header` (from parsing the HTTP/1 request
+/// that follows) are consulted for an `Upgrade: h2c` request instead.
+///
+/// This is the integration point the `http_port` accept loop calls into,
+/// the same way it calls [`crate::net::socket::apply_tcp_tuning`] for
+/// socket-level tuning.
+pub fn negotiate_protocol(
+    h2c_enabled: bool,
+    peeked: &[u8],
+    connection_header: Option<&str>,
+    upgrade_header: Option<&str>,
+) -> ConnectionProtocol {
+    if !h2c_enabled {
+        return ConnectionProtocol::Http1;
+    }
+    if is_h2_prior_knowledge(peeked) {
+        return ConnectionProtocol::Http2PriorKnowledge;
+    }
+    if wants_h2c_upgrade(connection_header, upgrade_header) {
+        return ConnectionProtocol::Http2Upgrade;
+    }
+    ConnectionProtocol::Http1
+}
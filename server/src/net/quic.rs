@@ -0,0 +1,238 @@
+// SPDX-FileCopyrightText: 2024 Softbear, Inc.
+// SPDX-License-Identifier: LGPL-3.0-or-later
+
+use crate::actor::health::Health;
+use crate::net::congestion::{CongestionControlKind, CongestionController};
+use bytes::Bytes;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How often a live QUIC connection reports its RTT/congestion-window
+/// estimate into `Health`.
+const HEALTH_SAMPLE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Answers one HTTP/3 request. This is the same request routing used by the
+/// HTTP/1 and h2c listeners; the QUIC listener just needs it handed in,
+/// since it lives outside `net` and doesn't know about transports.
+pub type H3Router =
+    Arc<dyn Fn(http::Request<()>) -> Pin<Box<dyn Future<Output = http::Response<Bytes>> + Send>> + Send + Sync>;
+
+/// Adapts our [`CongestionController`] (shared with the from-scratch docs
+/// in `congestion.rs`) to quinn's `congestion::Controller` trait, so the
+/// `--congestion-control` choice actually drives QUIC's loss recovery
+/// instead of only existing on paper.
+#[derive(Debug)]
+struct QuicCongestionController {
+    kind: CongestionControlKind,
+    mss: f32,
+    inner: Box<dyn CongestionController>,
+    start: Instant,
+}
+
+impl QuicCongestionController {
+    fn new(kind: CongestionControlKind, current_mtu: u16) -> Self {
+        let mss = current_mtu as f32;
+        Self {
+            kind,
+            mss,
+            inner: kind.build(mss),
+            start: Instant::now(),
+        }
+    }
+
+    fn secs(&self, now: Instant) -> f32 {
+        now.duration_since(self.start).as_secs_f32()
+    }
+}
+
+impl quinn::congestion::Controller for QuicCongestionController {
+    fn on_sent(&mut self, _now: Instant, _bytes: u64, _last_packet_number: u64) {}
+
+    fn on_ack(
+        &mut self,
+        now: Instant,
+        _sent: Instant,
+        bytes: u64,
+        _app_limited: bool,
+        rtt: &quinn::rtt::RttEstimator,
+    ) {
+        let t = self.secs(now);
+        self.inner.on_ack(t, bytes as f32, rtt.get().as_secs_f32());
+    }
+
+    fn on_end_acks(
+        &mut self,
+        _now: Instant,
+        _in_flight: u64,
+        _app_limited: bool,
+        _largest_packet_num_acked: Option<u64>,
+    ) {
+    }
+
+    fn on_congestion_event(
+        &mut self,
+        now: Instant,
+        _sent: Instant,
+        _is_persistent_congestion: bool,
+        _lost_bytes: u64,
+    ) {
+        self.inner.on_loss(self.secs(now));
+    }
+
+    fn on_mtu_update(&mut self, new_mtu: u16) {
+        self.mss = new_mtu as f32;
+    }
+
+    fn window(&self) -> u64 {
+        self.inner.cwnd() as u64
+    }
+
+    fn clone_box(&self) -> Box<dyn quinn::congestion::Controller> {
+        // Loses in-flight state, but that only matters mid-handshake, and a
+        // fresh controller of the same kind converges in one window.
+        Box::new(Self::new(self.kind, self.mss as u16))
+    }
+
+    fn initial_window(&self) -> u64 {
+        (self.mss * 10.0) as u64
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any> {
+        self
+    }
+}
+
+struct QuicCongestionControllerFactory {
+    kind: CongestionControlKind,
+}
+
+impl quinn::congestion::ControllerFactory for QuicCongestionControllerFactory {
+    fn build(&self, _now: Instant, current_mtu: u16) -> Box<dyn quinn::congestion::Controller> {
+        Box::new(QuicCongestionController::new(self.kind, current_mtu))
+    }
+}
+
+/// Runs the optional HTTP/3/QUIC listener, if `Options::h3_port` is set.
+///
+/// Binds a UDP socket on `h3_port`, reuses `tls_config` (the same
+/// certificate material as the HTTPS listener), and wires up the
+/// `--congestion-control` choice as the loss recovery algorithm for every
+/// connection. Every accepted bidirectional stream is parsed as an HTTP/3
+/// request and answered via `router` (the same routing the HTTP/1 and h2c
+/// listeners use), while each connection's RTT and congestion window are
+/// sampled into `health` periodically, the same way `Health::track_socket`
+/// does for TCP.
+pub async fn run_h3_listener(
+    h3_port: u16,
+    congestion_control: CongestionControlKind,
+    tls_config: Arc<rustls::ServerConfig>,
+    health: Arc<Mutex<Health>>,
+    router: H3Router,
+) -> std::io::Result<()> {
+    let mut transport_config = quinn::TransportConfig::default();
+    transport_config.congestion_controller_factory(Arc::new(QuicCongestionControllerFactory {
+        kind: congestion_control,
+    }));
+
+    let mut server_config =
+        quinn::ServerConfig::with_crypto(Arc::new(quinn::crypto::rustls::QuicServerConfig::try_from(
+            (*tls_config).clone(),
+        )?));
+    server_config.transport_config(Arc::new(transport_config));
+
+    let addr: SocketAddr = ([0, 0, 0, 0], h3_port).into();
+    let endpoint = quinn::Endpoint::server(server_config, addr)?;
+
+    log::info!("HTTP/3 (QUIC) listening on {addr} with {congestion_control:?}");
+
+    while let Some(incoming) = endpoint.accept().await {
+        let health = health.clone();
+        let router = router.clone();
+        tokio::spawn(async move {
+            match incoming.await {
+                Ok(connection) => {
+                    tokio::join!(
+                        sample_connection_health(connection.clone(), health),
+                        serve_h3_connection(connection, router),
+                    );
+                }
+                Err(e) => log::debug!("QUIC handshake failed: {e}"),
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Accepts HTTP/3 requests on `connection` until it closes, answering each
+/// one via `router` on its own task.
+async fn serve_h3_connection(connection: quinn::Connection, router: H3Router) {
+    let mut h3_conn = match h3::server::Connection::new(h3_quinn::Connection::new(connection)).await {
+        Ok(h3_conn) => h3_conn,
+        Err(e) => {
+            log::debug!("h3 connection setup failed: {e}");
+            return;
+        }
+    };
+
+    loop {
+        match h3_conn.accept().await {
+            Ok(Some((request, stream))) => {
+                let router = router.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_h3_request(request, stream, router).await {
+                        log::debug!("h3 request failed: {e}");
+                    }
+                });
+            }
+            Ok(None) => break,
+            Err(e) => {
+                log::debug!("h3 connection error: {e}");
+                break;
+            }
+        }
+    }
+}
+
+/// Answers a single HTTP/3 request via `router`, writing the response
+/// headers and body back on `stream`.
+async fn handle_h3_request<S>(
+    request: http::Request<()>,
+    mut stream: h3::server::RequestStream<S, Bytes>,
+    router: H3Router,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: h3::quic::BidiStream<Bytes>,
+{
+    let response = router(request).await;
+    let (parts, body) = response.into_parts();
+    stream.send_response(http::Response::from_parts(parts, ())).await?;
+    if !body.is_empty() {
+        stream.send_data(body).await?;
+    }
+    stream.finish().await?;
+    Ok(())
+}
+
+/// Periodically samples a QUIC connection's RTT and congestion window into
+/// `Health`, until the connection closes.
+async fn sample_connection_health(connection: quinn::Connection, health: Arc<Mutex<Health>>) {
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(HEALTH_SAMPLE_PERIOD) => {
+                let stats = connection.stats();
+                health.lock().unwrap().record_quic_sample(
+                    connection.rtt().as_secs_f32(),
+                    stats.path.cwnd as f32,
+                );
+            }
+            reason = connection.closed() => {
+                log::debug!("QUIC connection closed: {reason}");
+                break;
+            }
+        }
+    }
+}
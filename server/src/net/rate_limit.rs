@@ -0,0 +1,108 @@
+// SPDX-FileCopyrightText: 2024 Softbear, Inc.
+// SPDX-License-Identifier: LGPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Instant;
+
+/// Key a client is rate-limited by. IPv4 addresses are tracked individually;
+/// IPv6 addresses are collapsed to their /64 prefix so a client can't evade
+/// limits by rotating addresses within its own allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ClientKey {
+    V4(u32),
+    V6Prefix64(u64),
+}
+
+impl From<IpAddr> for ClientKey {
+    fn from(addr: IpAddr) -> Self {
+        // `to_canonical` turns IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`)
+        // back into `V4`; otherwise they'd all share the zero /64 prefix and
+        // rate-limit each other on a dual-stack listener.
+        match addr.to_canonical() {
+            IpAddr::V4(v4) => Self::V4(u32::from(v4)),
+            IpAddr::V6(v6) => Self::V6Prefix64(u64::from_be_bytes(
+                v6.octets()[..8].try_into().unwrap(),
+            )),
+        }
+    }
+}
+
+/// One client's token bucket. Kept intentionally small (8 bytes) since one
+/// of these exists per active client key.
+struct Bucket {
+    /// Current allowance, in the same units as `cost`.
+    allowance: f32,
+    /// Seconds since the limiter was created, as of the last check.
+    last_checked: f32,
+}
+
+/// Per-client token-bucket rate limiter.
+///
+/// Each bucket refills continuously at `rate` per second up to `burst`, and
+/// is evicted once it refills back to full, since a full bucket carries no
+/// state worth keeping.
+pub struct RateLimiter {
+    rate: f32,
+    burst: f32,
+    start: Instant,
+    buckets: HashMap<ClientKey, Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(rate: f32, burst: f32) -> Self {
+        Self {
+            rate,
+            burst,
+            start: Instant::now(),
+            buckets: HashMap::new(),
+        }
+    }
+
+    fn now(&self) -> f32 {
+        self.start.elapsed().as_secs_f32()
+    }
+
+    /// Returns `true` if `cost` may be admitted for `addr`, deducting it
+    /// from that client's allowance. Returns `false` (and leaves the
+    /// allowance untouched) if the client doesn't have enough left.
+    pub fn check(&mut self, addr: IpAddr, cost: f32) -> bool {
+        let now = self.now();
+        let key = ClientKey::from(addr);
+        let bucket = self.buckets.entry(key).or_insert(Bucket {
+            allowance: self.burst,
+            last_checked: now,
+        });
+
+        let elapsed = (now - bucket.last_checked).max(0.0);
+        bucket.allowance = (bucket.allowance + elapsed * self.rate).min(self.burst);
+        bucket.last_checked = now;
+
+        if bucket.allowance < cost {
+            return false;
+        }
+        bucket.allowance -= cost;
+        true
+    }
+
+    /// Evicts buckets that have refilled back to full, since they carry no
+    /// state worth keeping. Call this periodically (e.g. every few minutes).
+    pub fn sweep(&mut self) {
+        let now = self.now();
+        let (rate, burst) = (self.rate, self.burst);
+        self.buckets.retain(|_, bucket| {
+            let elapsed = (now - bucket.last_checked).max(0.0);
+            bucket.allowance + elapsed * rate < burst
+        });
+    }
+
+    /// Number of clients currently tracked.
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Whether any clients are currently tracked.
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+}
@@ -0,0 +1,56 @@
+// SPDX-FileCopyrightText: 2024 Softbear, Inc.
+// SPDX-License-Identifier: LGPL-3.0-or-later
+
+use std::io;
+use std::os::unix::io::RawFd;
+
+fn setsockopt<T>(fd: RawFd, level: i32, name: i32, value: T) -> io::Result<()> {
+    let ret = unsafe {
+        libc::setsockopt(
+            fd,
+            level,
+            name,
+            &value as *const T as *const libc::c_void,
+            std::mem::size_of::<T>() as libc::socklen_t,
+        )
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Applies `Options`' TCP tuning flags to a listening socket:
+/// - `fast_open_backlog`: enables `TCP_FASTOPEN` with the given pending
+///   SYN queue length, cutting handshake RTTs for reconnecting clients.
+/// - `keepalive_secs`: enables `SO_KEEPALIVE` with that idle time (and a
+///   matching probe interval), so dead NAT-bound clients are reaped. The
+///   idle time and interval are only set on Linux; elsewhere only
+///   `SO_KEEPALIVE` is enabled.
+/// - `send_buffer_size`: overrides `SO_SNDBUF`.
+pub fn apply_tcp_tuning(
+    fd: RawFd,
+    fast_open_backlog: Option<u32>,
+    keepalive_secs: Option<u32>,
+    send_buffer_size: Option<u32>,
+) -> io::Result<()> {
+    if let Some(backlog) = fast_open_backlog {
+        setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_FASTOPEN, backlog as i32)?;
+    }
+
+    if let Some(idle_secs) = keepalive_secs {
+        setsockopt(fd, libc::SOL_SOCKET, libc::SO_KEEPALIVE, 1i32)?;
+        #[cfg(target_os = "linux")]
+        {
+            setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_KEEPIDLE, idle_secs as i32)?;
+            setsockopt(fd, libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, idle_secs as i32)?;
+        }
+    }
+
+    if let Some(size) = send_buffer_size {
+        setsockopt(fd, libc::SOL_SOCKET, libc::SO_SNDBUF, size as i32)?;
+    }
+
+    Ok(())
+}